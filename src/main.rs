@@ -1,37 +1,76 @@
+use anyhow::Context;
+use argon2::{
+    password_hash::{PasswordHash, PasswordVerifier},
+    Argon2,
+};
 use axum::{
+    async_trait,
     body::HttpBody,
-    extract::State,
-    http::StatusCode,
+    extract::{FromRequestParts, State},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
     routing::{get, post},
-    Extension, Json, Router,
+    Json, Router,
 };
 use axum_auth::AuthBasic;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
 use dotenvy::dotenv;
-use mysql::{prelude::Queryable, Conn, Opts, Pool, PooledConn};
+use mysql::{
+    consts::{ColumnFlags, ColumnType},
+    prelude::Queryable,
+    Column, Opts, Pool, PooledConn, Value,
+};
 use serde::{Deserialize, Serialize};
-use serde_json::{json, Value};
+use serde_json::json;
 use std::{
-    any::Any,
     collections::HashMap,
-    env,
-    sync::{Arc, RwLock},
+    env, fs,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
 };
 use tower::ServiceBuilder;
-use tower_http::{add_extension::AddExtensionLayer, cors::CorsLayer, trace::TraceLayer};
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
 use uuid::{uuid, Uuid};
 
-#[derive(Default, Debug, Serialize, Deserialize)]
-struct Config {
+/// How long a session's pinned connection may sit idle before the sweeper
+/// reclaims it, e.g. a client that sent `BEGIN` and never followed up with
+/// `COMMIT`/`ROLLBACK`.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the sweeper scans for idle sessions.
+const SESSION_SWEEP_INTERVAL: Duration = Duration::from_secs(10);
+/// Path to the TOML config file, overridable for tests/deployments.
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// One named database/branch this server fronts. `username` is the Basic
+/// auth identity clients authenticate as; `password_hash` is its Argon2
+/// PHC string, never the plaintext secret.
+#[derive(Debug, Clone, Deserialize)]
+struct TenantConfig {
+    pub name: String,
     pub connection_url: String,
     pub username: String,
-    pub password: String,
+    pub password_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Config {
     pub port: u32,
+    pub tenant: Vec<TenantConfig>,
+}
+
+impl Config {
+    fn load(path: &str) -> anyhow::Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file at {path}"))?;
+        toml::from_str(&raw).with_context(|| format!("failed to parse config file at {path}"))
+    }
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
 struct Field {
     pub name: String,
-    pub _type: String,
+    #[serde(rename = "type")]
+    pub r#type: String,
     pub table: Option<String>,
 
     pub database: Option<String>,
@@ -56,6 +95,133 @@ struct ResultRes {
     pub fields: Option<Vec<Field>>,
     pub rows: Option<Vec<Row>>,
 }
+/// Maps a MySQL column's wire type (plus its unsigned/binary flags) to the
+/// Vitess type string the `@planetscale/database` driver expects.
+fn vitess_column_type(column: &Column) -> String {
+    let flags = column.flags();
+    let unsigned = flags.contains(ColumnFlags::UNSIGNED_FLAG);
+    let binary = flags.contains(ColumnFlags::BINARY_FLAG);
+
+    match column.column_type() {
+        ColumnType::MYSQL_TYPE_DECIMAL | ColumnType::MYSQL_TYPE_NEWDECIMAL => "DECIMAL",
+        ColumnType::MYSQL_TYPE_TINY => {
+            if unsigned {
+                "UINT8"
+            } else {
+                "INT8"
+            }
+        }
+        ColumnType::MYSQL_TYPE_SHORT => {
+            if unsigned {
+                "UINT16"
+            } else {
+                "INT16"
+            }
+        }
+        ColumnType::MYSQL_TYPE_INT24 => {
+            if unsigned {
+                "UINT24"
+            } else {
+                "INT24"
+            }
+        }
+        ColumnType::MYSQL_TYPE_LONG => {
+            if unsigned {
+                "UINT32"
+            } else {
+                "INT32"
+            }
+        }
+        ColumnType::MYSQL_TYPE_LONGLONG => {
+            if unsigned {
+                "UINT64"
+            } else {
+                "INT64"
+            }
+        }
+        ColumnType::MYSQL_TYPE_FLOAT => "FLOAT32",
+        ColumnType::MYSQL_TYPE_DOUBLE => "FLOAT64",
+        ColumnType::MYSQL_TYPE_NULL => "NULL",
+        ColumnType::MYSQL_TYPE_TIMESTAMP | ColumnType::MYSQL_TYPE_TIMESTAMP2 => "TIMESTAMP",
+        ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE => "DATE",
+        ColumnType::MYSQL_TYPE_TIME | ColumnType::MYSQL_TYPE_TIME2 => "TIME",
+        ColumnType::MYSQL_TYPE_DATETIME | ColumnType::MYSQL_TYPE_DATETIME2 => "DATETIME",
+        ColumnType::MYSQL_TYPE_YEAR => "YEAR",
+        ColumnType::MYSQL_TYPE_BIT => "BIT",
+        ColumnType::MYSQL_TYPE_JSON => "JSON",
+        ColumnType::MYSQL_TYPE_ENUM => "ENUM",
+        ColumnType::MYSQL_TYPE_SET => "SET",
+        ColumnType::MYSQL_TYPE_GEOMETRY => "GEOMETRY",
+        ColumnType::MYSQL_TYPE_TINY_BLOB
+        | ColumnType::MYSQL_TYPE_MEDIUM_BLOB
+        | ColumnType::MYSQL_TYPE_LONG_BLOB
+        | ColumnType::MYSQL_TYPE_BLOB => {
+            if binary {
+                "BLOB"
+            } else {
+                "TEXT"
+            }
+        }
+        ColumnType::MYSQL_TYPE_VARCHAR | ColumnType::MYSQL_TYPE_VAR_STRING => {
+            if binary {
+                "VARBINARY"
+            } else {
+                "VARCHAR"
+            }
+        }
+        ColumnType::MYSQL_TYPE_STRING => {
+            if binary {
+                "BINARY"
+            } else {
+                "CHAR"
+            }
+        }
+        _ => "EXPRESSION",
+    }
+    .to_string()
+}
+
+/// Renders a MySQL value as the bytes the PlanetScale wire protocol packs
+/// into `Row::values`, i.e. its text-protocol representation. Returns
+/// `None` for SQL `NULL`, which contributes a `"-1"` length and no bytes.
+fn value_to_bytes(value: &Value) -> Option<Vec<u8>> {
+    match value {
+        Value::NULL => None,
+        Value::Bytes(bytes) => Some(bytes.clone()),
+        Value::Int(i) => Some(i.to_string().into_bytes()),
+        Value::UInt(u) => Some(u.to_string().into_bytes()),
+        Value::Float(f) => Some(f.to_string().into_bytes()),
+        Value::Double(d) => Some(d.to_string().into_bytes()),
+        Value::Date(year, month, day, hour, minute, second, micros) => {
+            let text = if *micros == 0 {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+                    year, month, day, hour, minute, second
+                )
+            } else {
+                format!(
+                    "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:06}",
+                    year, month, day, hour, minute, second, micros
+                )
+            };
+            Some(text.into_bytes())
+        }
+        Value::Time(negative, days, hours, minutes, seconds, micros) => {
+            let sign = if *negative { "-" } else { "" };
+            let total_hours = u64::from(*days) * 24 + u64::from(*hours);
+            let text = if *micros == 0 {
+                format!("{}{:02}:{:02}:{:02}", sign, total_hours, minutes, seconds)
+            } else {
+                format!(
+                    "{}{:02}:{:02}:{:02}.{:06}",
+                    sign, total_hours, minutes, seconds, micros
+                )
+            };
+            Some(text.into_bytes())
+        }
+    }
+}
+
 #[derive(Default, Debug, Serialize, Deserialize)]
 struct Error {
     pub message: String,
@@ -89,12 +255,226 @@ impl ResponseBody {
     }
 }
 
+/// Unifies every way a request can fail into one type so handlers can use
+/// `?` instead of hand-building `ResponseBody::from_error` at each call
+/// site. Each variant carries the `session` it failed under so the client
+/// still gets back the session it sent, the way a successful response
+/// would. Auth failures are the exception: they are raised before the body
+/// carrying `session` is read, so they carry a freshly minted one.
+#[derive(Debug, thiserror::Error)]
+enum ApiError {
+    #[error("invalid credentials")]
+    InvalidCredentials { session: Uuid },
+    #[error("missing query")]
+    MissingQuery { session: Uuid },
+    #[error("database error: {source}")]
+    Database { session: Uuid, source: mysql::Error },
+    #[error("connection pool exhausted")]
+    PoolExhausted { session: Uuid },
+    #[error("internal error: {message}")]
+    Internal { session: Uuid, message: String },
+}
+
+impl ApiError {
+    fn session(&self) -> Uuid {
+        match *self {
+            ApiError::InvalidCredentials { session }
+            | ApiError::MissingQuery { session }
+            | ApiError::Database { session, .. }
+            | ApiError::PoolExhausted { session }
+            | ApiError::Internal { session, .. } => session,
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            ApiError::InvalidCredentials { .. } => StatusCode::UNAUTHORIZED,
+            ApiError::MissingQuery { .. } => StatusCode::BAD_REQUEST,
+            ApiError::Database { .. } | ApiError::Internal { .. } => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ApiError::PoolExhausted { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// PlanetScale's numeric `error.code`, distinct from the HTTP status.
+    fn code(&self) -> u32 {
+        match self {
+            ApiError::InvalidCredentials { .. } => 401,
+            ApiError::MissingQuery { .. } => 400,
+            ApiError::Database { .. } | ApiError::Internal { .. } => 500,
+            ApiError::PoolExhausted { .. } => 503,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+        let session = self.session();
+        // `Internal` messages describe server misconfiguration (bad password
+        // hashes, missing pools, poisoned locks) that callers have no
+        // business seeing; log the specifics here and send back something
+        // generic instead.
+        let message = if let ApiError::Internal { message, .. } = &self {
+            eprintln!("internal error (session {}): {}", session, message);
+            "internal server error".to_string()
+        } else {
+            self.to_string()
+        };
+        (
+            status,
+            Json(ResponseBody::from_error(Error { message, code }, session)),
+        )
+            .into_response()
+    }
+}
+
+/// A connection checked out of the pool and pinned to one PlanetScale
+/// session for the lifetime of a multi-statement transaction, i.e. from
+/// `BEGIN` until the matching `COMMIT`/`ROLLBACK` (or until the idle
+/// sweeper reclaims it). Held behind its own `Mutex` so the session map's
+/// lock only ever guards the lookup/insert/remove, never the DB round trip
+/// itself — otherwise every statement on every pinned session would
+/// serialize on one global lock.
+struct PinnedConnection {
+    conn: PooledConn,
+    last_used: Instant,
+    tenant: String,
+}
+
+/// A session slot shared between the map and whichever request currently
+/// holds its connection.
+type SessionSlot = Arc<Mutex<PinnedConnection>>;
+
 struct AppState {
-    pub config: Config,
+    pub tenants_by_username: HashMap<String, TenantConfig>,
+    pub pools: HashMap<String, Pool>,
+    pub sessions: RwLock<HashMap<Uuid, SessionSlot>>,
 }
 
 type SharedState = Arc<RwLock<AppState>>;
 
+/// The tenant a request authenticated as, resolved once from the Basic auth
+/// header so `execute`/`session` don't each re-implement the 401 path.
+struct AuthedTenant {
+    tenant: String,
+    pool: Pool,
+}
+
+#[async_trait]
+impl FromRequestParts<SharedState> for AuthedTenant {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &SharedState,
+    ) -> Result<Self, Self::Rejection> {
+        // The session isn't known yet at this point: Basic auth lives in
+        // the headers, while `session` travels in the JSON body, which is
+        // extracted after this runs. Auth failures report a freshly minted
+        // session rather than the client's, the same way CreateSession did
+        // before a client ever had one.
+        let unauthenticated = || ApiError::InvalidCredentials {
+            session: Uuid::new_v4(),
+        };
+
+        let AuthBasic((username, password)) = AuthBasic::from_request_parts(parts, state)
+            .await
+            .map_err(|_| unauthenticated())?;
+        let password = password.unwrap_or_default();
+
+        let (tenant_name, password_hash, pool) = {
+            let app_state = state.read().map_err(|_| ApiError::Internal {
+                session: Uuid::new_v4(),
+                message: "state lock poisoned".to_string(),
+            })?;
+            let tenant = app_state
+                .tenants_by_username
+                .get(&username)
+                .ok_or_else(unauthenticated)?;
+            let pool = app_state
+                .pools
+                .get(&tenant.name)
+                .cloned()
+                .ok_or_else(|| ApiError::Internal {
+                    session: Uuid::new_v4(),
+                    message: format!("no connection pool configured for tenant {}", tenant.name),
+                })?;
+            (tenant.name.clone(), tenant.password_hash.clone(), pool)
+        };
+
+        // Argon2 is deliberately slow, so verification runs on a blocking
+        // thread rather than stealing a Tokio worker for the duration of the
+        // hash computation, the same reasoning that pins DB I/O to
+        // `spawn_blocking` elsewhere.
+        tokio::task::spawn_blocking(move || {
+            let parsed_hash = PasswordHash::new(&password_hash).map_err(|err| ApiError::Internal {
+                session: Uuid::new_v4(),
+                message: format!(
+                    "invalid password hash configured for tenant {}: {}",
+                    tenant_name, err
+                ),
+            })?;
+            Argon2::default()
+                .verify_password(password.as_bytes(), &parsed_hash)
+                .map_err(|_| unauthenticated())
+        })
+        .await
+        .map_err(|_| ApiError::Internal {
+            session: Uuid::new_v4(),
+            message: "auth task panicked".to_string(),
+        })??;
+
+        Ok(AuthedTenant {
+            tenant: tenant_name,
+            pool,
+        })
+    }
+}
+
+fn is_begin_statement(query: &str) -> bool {
+    let query = query.trim_start().to_ascii_uppercase();
+    query.starts_with("BEGIN") || query.starts_with("START TRANSACTION")
+}
+
+fn is_commit_or_rollback_statement(query: &str) -> bool {
+    let query = query.trim_start().to_ascii_uppercase();
+    query.starts_with("COMMIT") || query.starts_with("ROLLBACK")
+}
+
+/// Spawns a background task that periodically drops pinned connections
+/// whose session has been idle past `SESSION_IDLE_TIMEOUT`, returning them
+/// to the pool. A poisoned state or session-map lock just means this tick
+/// does nothing; it doesn't panic the sweeper or the process. A session
+/// whose own slot lock is poisoned is dropped.
+fn spawn_session_sweeper(state: SharedState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SESSION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let state = state.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                let Ok(app_state) = state.read() else {
+                    return;
+                };
+                let Ok(mut sessions) = app_state.sessions.write() else {
+                    return;
+                };
+                sessions.retain(|_, slot| match slot.try_lock() {
+                    Ok(pinned) => pinned.last_used.elapsed() < SESSION_IDLE_TIMEOUT,
+                    // Held by a request mid-query, so not idle; waiting on it
+                    // here would hold the map lock across its DB round trip.
+                    Err(std::sync::TryLockError::WouldBlock) => true,
+                    Err(std::sync::TryLockError::Poisoned(_)) => false,
+                });
+            })
+            .await;
+        }
+    });
+}
+
 #[derive(Default, Serialize, Deserialize)]
 struct RequestBody {
     pub query: Option<String>,
@@ -120,89 +500,216 @@ async fn health(
         None => Json(RequestBody::default()),
     };
     Json(ResponseBody {
-        session: body.session.unwrap(),
+        session: body.session.unwrap_or_else(Uuid::new_v4),
         error: None,
         result: None,
         timing: None,
     })
 }
+/// Runs `query` to completion on `conn` and builds the Vitess `ResultRes`
+/// payload. Blocks on synchronous `mysql` I/O, so callers must run it
+/// through `spawn_blocking` rather than calling it from async code
+/// directly.
+fn run_query_on_conn(conn: &mut PooledConn, query: &str) -> mysql::Result<ResultRes> {
+    let mut rows_iter = conn.query_iter(query)?;
+
+    let fields: Vec<Field> = rows_iter
+        .columns()
+        .as_ref()
+        .iter()
+        .map(|column| Field {
+            name: column.name_str().to_string(),
+            r#type: vitess_column_type(column),
+            table: Some(column.table_str().to_string()),
+            database: Some(column.schema_str().to_string()),
+            orgTable: Some(column.org_table_str().to_string()),
+            orgName: Some(column.org_name_str().to_string()),
+            columnLength: Some(column.column_length()),
+            charset: Some(column.character_set() as u32),
+            flags: Some(u32::from(column.flags().bits())),
+            // The `mysql` crate doesn't expose the raw DDL-level column type
+            // (e.g. "varchar(255)") that this field documents, only the
+            // protocol-level type already captured in `type` above, so leave
+            // it unset rather than faking it with a duplicate.
+            columnType: None,
+        })
+        .collect();
+
+    let mut rows = Vec::new();
+    while let Some(row) = rows_iter.next() {
+        let row = row?;
+        let mut lengths = Vec::with_capacity(row.len());
+        let mut values = Vec::new();
+        for i in 0..row.len() {
+            match row.as_ref(i).and_then(value_to_bytes) {
+                Some(bytes) => {
+                    lengths.push(bytes.len().to_string());
+                    values.extend(bytes);
+                }
+                None => lengths.push("-1".to_string()),
+            }
+        }
+        rows.push(Row {
+            lengths,
+            values: Some(STANDARD.encode(values)),
+        });
+    }
+
+    Ok(ResultRes {
+        rowsAffected: Some(rows_iter.affected_rows().to_string()),
+        insertId: Some(rows_iter.last_insert_id().unwrap_or(0).to_string()),
+        fields: Some(fields),
+        rows: Some(rows),
+    })
+}
+
+/// Everything that can go wrong inside `run_session_query`, kept separate
+/// from `mysql::Error` so a poisoned lock can't masquerade as a database
+/// error.
+#[derive(Debug, thiserror::Error)]
+enum SessionQueryError {
+    #[error("database error: {0}")]
+    Database(#[from] mysql::Error),
+    #[error("state lock poisoned")]
+    PoisonedLock,
+    #[error("session belongs to a different tenant")]
+    TenantMismatch,
+}
+
+/// Rejects reuse of a session pinned by one tenant from a request
+/// authenticated as another, even when the session UUID collides. Split out
+/// from `run_session_query` so this exact check — the regression introduced
+/// in the original session-pinning commit and only caught in the
+/// tenant-scoping follow-up — can be unit tested without a live connection.
+fn check_session_tenant(pinned_tenant: &str, requesting_tenant: &str) -> Result<(), SessionQueryError> {
+    if pinned_tenant != requesting_tenant {
+        return Err(SessionQueryError::TenantMismatch);
+    }
+    Ok(())
+}
+
+/// Routes `query` to the right connection for `session`: a pinned
+/// connection if one is already held open for it, a freshly pinned one if
+/// `query` opens a transaction, otherwise a plain connection borrowed from
+/// the pool for just this statement. Blocks on synchronous `mysql`/lock
+/// I/O, so callers must run it through `spawn_blocking`.
+///
+/// The session map's lock is only ever held to look up, insert, or remove
+/// a slot — never across the DB round trip, so two sessions (or two
+/// tenants) with open transactions can still make progress concurrently.
+///
+/// `tenant` is the name of the tenant `AuthedTenant` authenticated this
+/// request against. A session UUID pinned by one tenant is never handed to
+/// a request authenticated as another, even if the UUID happens to collide.
+fn run_session_query(
+    state: &SharedState,
+    pool: &Pool,
+    tenant: &str,
+    session: Uuid,
+    query: &str,
+) -> Result<ResultRes, SessionQueryError> {
+    let existing_slot = {
+        let app_state = state.read().map_err(|_| SessionQueryError::PoisonedLock)?;
+        let sessions = app_state
+            .sessions
+            .read()
+            .map_err(|_| SessionQueryError::PoisonedLock)?;
+        sessions.get(&session).cloned()
+    };
+
+    if let Some(slot) = existing_slot {
+        let result = {
+            let mut pinned = slot.lock().map_err(|_| SessionQueryError::PoisonedLock)?;
+            check_session_tenant(&pinned.tenant, tenant)?;
+            pinned.last_used = Instant::now();
+            run_query_on_conn(&mut pinned.conn, query)?
+        };
+        if is_commit_or_rollback_statement(query) {
+            let app_state = state.read().map_err(|_| SessionQueryError::PoisonedLock)?;
+            let mut sessions = app_state
+                .sessions
+                .write()
+                .map_err(|_| SessionQueryError::PoisonedLock)?;
+            sessions.remove(&session);
+        }
+        return Ok(result);
+    }
+
+    if is_begin_statement(query) {
+        let mut conn = pool.get_conn()?;
+        let result = run_query_on_conn(&mut conn, query)?;
+        let app_state = state.read().map_err(|_| SessionQueryError::PoisonedLock)?;
+        let mut sessions = app_state
+            .sessions
+            .write()
+            .map_err(|_| SessionQueryError::PoisonedLock)?;
+        sessions.insert(
+            session,
+            Arc::new(Mutex::new(PinnedConnection {
+                conn,
+                last_used: Instant::now(),
+                tenant: tenant.to_string(),
+            })),
+        );
+        return Ok(result);
+    }
+
+    let mut conn = pool.get_conn()?;
+    Ok(run_query_on_conn(&mut conn, query)?)
+}
+
 async fn execute(
     State(state): State<SharedState>,
-    AuthBasic((username, password)): AuthBasic,
-    Extension(pool): Extension<Pool>,
+    authed: AuthedTenant,
     Json(body): Json<RequestBody>,
-) -> Json<ResponseBody> {
-    let session = match body.session {
-        Some(s) => s,
-        None => Uuid::new_v4(),
-    };
-    let password = match password {
-        Some(p) => p,
-        None => "".to_string(),
-    };
-    if username != state.read().unwrap().config.username
-        || password != state.read().unwrap().config.password
-    {
-        return Json(ResponseBody::from_error(
-            Error {
-                message: "Invalid credentials".to_string(),
-                code: 401,
-            },
+) -> Result<Json<ResponseBody>, ApiError> {
+    let session = body.session.unwrap_or_else(Uuid::new_v4);
+
+    let query = body
+        .query
+        .filter(|q| !q.is_empty())
+        .ok_or(ApiError::MissingQuery { session })?;
+
+    let state_for_query = state.clone();
+    let result = tokio::task::spawn_blocking(move || {
+        run_session_query(&state_for_query, &authed.pool, &authed.tenant, session, &query)
+    })
+    .await
+    .map_err(|_| ApiError::Internal {
+        session,
+        message: "query task panicked".to_string(),
+    })?
+    .map_err(|source| match source {
+        SessionQueryError::PoisonedLock => ApiError::Internal {
             session,
-        ));
-    }
-    let mut conn = pool.get_conn().unwrap();
-    let query = body.query.unwrap_or("".to_string());
-    let res: Vec<String> = match conn.query(query) {
-        Ok(e) => e.to_vec(),
-        Err(e) => {
-            let arr: Vec<String> = Vec::new();
-            arr
+            message: "session state lock poisoned".to_string(),
+        },
+        SessionQueryError::TenantMismatch => ApiError::InvalidCredentials { session },
+        SessionQueryError::Database(source) => {
+            if source.to_string().to_lowercase().contains("timeout") {
+                ApiError::PoolExhausted { session }
+            } else {
+                ApiError::Database { session, source }
+            }
         }
-    };
-    println!("{:?}", res);
-    Json(ResponseBody {
+    })?;
+
+    Ok(Json(ResponseBody {
         session,
-        result: Some(ResultRes {
-            fields: None,
-            insertId: None,
-            rows: None,
-            rowsAffected: None,
-        }),
+        result: Some(result),
         timing: None,
         error: None,
-    })
+    }))
 }
 
-async fn session(
-    State(state): State<SharedState>,
-    AuthBasic((username, password)): AuthBasic,
-) -> Json<ResponseBody> {
-    let session = Uuid::new_v4();
-    let password = match password {
-        Some(p) => p,
-        None => "".to_string(),
-    };
-    if username != state.read().unwrap().config.username
-        || password != state.read().unwrap().config.password
-    {
-        return Json(ResponseBody::from_error(
-            Error {
-                message: "Invalid credentials".to_string(),
-                code: 401,
-            },
-            session,
-        ));
-    }
-
-    Json(ResponseBody::from_session(session))
+async fn session(_authed: AuthedTenant) -> Result<Json<ResponseBody>, ApiError> {
+    Ok(Json(ResponseBody::from_session(Uuid::new_v4())))
 }
 
-async fn app(state: AppState, pool: Pool) -> anyhow::Result<Router> {
+async fn app(state: SharedState) -> anyhow::Result<Router> {
     let middleware = ServiceBuilder::new()
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::permissive())
-        .layer(AddExtensionLayer::new(pool))
         .into_inner();
     let router = Router::new()
         .route("/", get(|| async { Json(json!({"status": "ok"})) }))
@@ -210,7 +717,7 @@ async fn app(state: AppState, pool: Pool) -> anyhow::Result<Router> {
         .route("/psdb.v1alpha1.Database/Execute", post(execute))
         .route("/psdb.v1alpha1.Database/CreateSession", post(session))
         .layer(middleware)
-        .with_state(Arc::new(RwLock::new(state)));
+        .with_state(state);
 
     Ok(router)
 }
@@ -218,25 +725,217 @@ async fn app(state: AppState, pool: Pool) -> anyhow::Result<Router> {
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     dotenv().ok();
-    let config = Config {
-        connection_url: env::var("DATABASE_URL").unwrap(),
-        username: env::var("PS_USERNAME").unwrap(),
-        password: env::var("PS_PASSWORD").unwrap(),
-        port: env::var("PORT")
-            .unwrap_or("3000".to_string())
-            .parse::<u32>()
-            .unwrap(),
+    let config_path = env::var("CONFIG_PATH").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+    let config = Config::load(&config_path)?;
+    let port = config.port;
+
+    let mut tenants_by_username = HashMap::new();
+    let mut pools = HashMap::new();
+    for tenant in config.tenant {
+        let opts = Opts::from_url(&tenant.connection_url)
+            .with_context(|| format!("invalid connection_url for tenant {}", tenant.name))?;
+        let pool = Pool::new(opts)
+            .with_context(|| format!("failed to create connection pool for tenant {}", tenant.name))?;
+        PasswordHash::new(&tenant.password_hash)
+            .map_err(|err| anyhow::anyhow!("{err}"))
+            .with_context(|| format!("invalid password_hash for tenant {}", tenant.name))?;
+        pools.insert(tenant.name.clone(), pool);
+        tenants_by_username.insert(tenant.username.clone(), tenant);
+    }
+
+    let app_state = AppState {
+        tenants_by_username,
+        pools,
+        sessions: RwLock::new(HashMap::new()),
     };
-    println!("{:?}", config);
-    let pool = Pool::new(Opts::from_url(&config.connection_url).unwrap()).unwrap();
-    let app_state = AppState { config };
+    let shared_state: SharedState = Arc::new(RwLock::new(app_state));
+    spawn_session_sweeper(shared_state.clone());
 
     let mut url = "0.0.0.0:".to_string();
-    url.push_str(&app_state.config.port.to_string());
+    url.push_str(&port.to_string());
     println!("Listening on {}", url);
 
-    axum::Server::bind(&url.parse().unwrap())
-        .serve(app(app_state, pool).await?.into_make_service())
+    axum::Server::bind(&url.parse().expect("invalid bind address"))
+        .serve(app(shared_state).await?.into_make_service())
         .await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_to_bytes_null_is_none() {
+        assert_eq!(value_to_bytes(&Value::NULL), None);
+    }
+
+    #[test]
+    fn value_to_bytes_bytes_passes_through() {
+        assert_eq!(
+            value_to_bytes(&Value::Bytes(b"hello".to_vec())),
+            Some(b"hello".to_vec())
+        );
+    }
+
+    #[test]
+    fn value_to_bytes_int_and_uint() {
+        assert_eq!(value_to_bytes(&Value::Int(-42)), Some(b"-42".to_vec()));
+        assert_eq!(value_to_bytes(&Value::UInt(42)), Some(b"42".to_vec()));
+    }
+
+    #[test]
+    fn value_to_bytes_float_and_double() {
+        assert_eq!(value_to_bytes(&Value::Float(1.5)), Some(b"1.5".to_vec()));
+        assert_eq!(value_to_bytes(&Value::Double(1.5)), Some(b"1.5".to_vec()));
+    }
+
+    #[test]
+    fn value_to_bytes_date_without_micros() {
+        let bytes = value_to_bytes(&Value::Date(2024, 1, 2, 3, 4, 5, 0)).unwrap();
+        assert_eq!(bytes, b"2024-01-02 03:04:05".to_vec());
+    }
+
+    #[test]
+    fn value_to_bytes_date_with_micros() {
+        let bytes = value_to_bytes(&Value::Date(2024, 1, 2, 3, 4, 5, 6)).unwrap();
+        assert_eq!(bytes, b"2024-01-02 03:04:05.000006".to_vec());
+    }
+
+    #[test]
+    fn value_to_bytes_time_without_micros() {
+        let bytes = value_to_bytes(&Value::Time(false, 1, 2, 3, 4, 0)).unwrap();
+        assert_eq!(bytes, b"26:03:04".to_vec());
+    }
+
+    #[test]
+    fn value_to_bytes_time_with_micros() {
+        let bytes = value_to_bytes(&Value::Time(false, 1, 2, 3, 4, 5)).unwrap();
+        assert_eq!(bytes, b"26:03:04.000005".to_vec());
+    }
+
+    #[test]
+    fn value_to_bytes_negative_time() {
+        let bytes = value_to_bytes(&Value::Time(true, 0, 2, 3, 4, 0)).unwrap();
+        assert_eq!(bytes, b"-02:03:04".to_vec());
+    }
+
+    #[test]
+    fn cross_tenant_session_reuse_is_rejected() {
+        assert!(matches!(
+            check_session_tenant("tenant-a", "tenant-b"),
+            Err(SessionQueryError::TenantMismatch)
+        ));
+    }
+
+    #[test]
+    fn same_tenant_session_reuse_is_allowed() {
+        assert!(check_session_tenant("tenant-a", "tenant-a").is_ok());
+    }
+
+    fn column_with(column_type: ColumnType, flags: ColumnFlags) -> Column {
+        Column::new(column_type).with_flags(flags)
+    }
+
+    #[test]
+    fn vitess_column_type_signed_and_unsigned_ints() {
+        assert_eq!(
+            vitess_column_type(&column_with(ColumnType::MYSQL_TYPE_TINY, ColumnFlags::empty())),
+            "INT8"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_TINY,
+                ColumnFlags::UNSIGNED_FLAG
+            )),
+            "UINT8"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(ColumnType::MYSQL_TYPE_SHORT, ColumnFlags::empty())),
+            "INT16"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_SHORT,
+                ColumnFlags::UNSIGNED_FLAG
+            )),
+            "UINT16"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(ColumnType::MYSQL_TYPE_INT24, ColumnFlags::empty())),
+            "INT24"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_INT24,
+                ColumnFlags::UNSIGNED_FLAG
+            )),
+            "UINT24"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(ColumnType::MYSQL_TYPE_LONG, ColumnFlags::empty())),
+            "INT32"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_LONG,
+                ColumnFlags::UNSIGNED_FLAG
+            )),
+            "UINT32"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_LONGLONG,
+                ColumnFlags::empty()
+            )),
+            "INT64"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_LONGLONG,
+                ColumnFlags::UNSIGNED_FLAG
+            )),
+            "UINT64"
+        );
+    }
+
+    #[test]
+    fn vitess_column_type_binary_vs_text() {
+        assert_eq!(
+            vitess_column_type(&column_with(ColumnType::MYSQL_TYPE_BLOB, ColumnFlags::empty())),
+            "TEXT"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_BLOB,
+                ColumnFlags::BINARY_FLAG
+            )),
+            "BLOB"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_VAR_STRING,
+                ColumnFlags::empty()
+            )),
+            "VARCHAR"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_VAR_STRING,
+                ColumnFlags::BINARY_FLAG
+            )),
+            "VARBINARY"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(ColumnType::MYSQL_TYPE_STRING, ColumnFlags::empty())),
+            "CHAR"
+        );
+        assert_eq!(
+            vitess_column_type(&column_with(
+                ColumnType::MYSQL_TYPE_STRING,
+                ColumnFlags::BINARY_FLAG
+            )),
+            "BINARY"
+        );
+    }
+}